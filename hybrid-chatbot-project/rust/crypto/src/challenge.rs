@@ -0,0 +1,41 @@
+//! HMAC-SHA256 challenge-response helpers for pre-shared-key deployments
+//! that can't run the full [`crate::handshake`] asymmetric handshake.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Which side of the exchange computed a given challenge response. Folding
+/// this into the HMAC input stops a response computed by one side from
+/// being reflected back as if the other side had sent it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChallengeRole {
+    Initiator,
+    Responder,
+}
+
+impl ChallengeRole {
+    fn direction_tag(self) -> &'static [u8] {
+        match self {
+            ChallengeRole::Initiator => b"initiator",
+            ChallengeRole::Responder => b"responder",
+        }
+    }
+
+    /// The other side's role.
+    pub fn opposite(self) -> Self {
+        match self {
+            ChallengeRole::Initiator => ChallengeRole::Responder,
+            ChallengeRole::Responder => ChallengeRole::Initiator,
+        }
+    }
+}
+
+/// Computes `HMAC-SHA256(key, nonce || direction_tag)`.
+pub fn compute_response(key: &[u8], nonce: &[u8], role: ChallengeRole) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts a key of any length");
+    mac.update(nonce);
+    mac.update(role.direction_tag());
+    mac.finalize().into_bytes().to_vec()
+}
@@ -0,0 +1,86 @@
+//! Self-describing wire format for [`crate::EncryptedMessage`].
+//!
+//! Every frame starts with a version byte and an algorithm identifier byte so
+//! a receiver never has to guess (or trust out-of-band configuration for)
+//! which cipher produced a message, and those header bytes are bound in as
+//! AEAD associated data so tampering with them fails authentication instead
+//! of silently corrupting parsing.
+
+use crate::{CryptoAlgorithm, EncryptedMessage};
+use anyhow::Result;
+
+/// Current frame format version.
+pub const FRAME_VERSION: u8 = 1;
+
+const HEADER_LEN: usize = 14; // version(1) + algorithm(1) + epoch(4) + counter(8)
+const IV_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Maps an algorithm to its on-the-wire identifier.
+pub fn algorithm_id(algorithm: &CryptoAlgorithm) -> u8 {
+    match algorithm {
+        CryptoAlgorithm::Aes256Gcm => 1,
+        CryptoAlgorithm::ChaCha20Poly1305 => 2,
+    }
+}
+
+/// Maps a wire identifier back to an algorithm.
+pub fn algorithm_from_id(id: u8) -> Result<CryptoAlgorithm> {
+    match id {
+        1 => Ok(CryptoAlgorithm::Aes256Gcm),
+        2 => Ok(CryptoAlgorithm::ChaCha20Poly1305),
+        other => Err(anyhow::anyhow!("unknown algorithm id {other}")),
+    }
+}
+
+/// Builds the `version || algorithm || epoch || counter` header bytes that
+/// are bound as AAD during encryption and decryption.
+pub fn header_bytes(algorithm: &CryptoAlgorithm, epoch: u32, counter: u64) -> [u8; HEADER_LEN] {
+    let mut header = [0u8; HEADER_LEN];
+    header[0] = FRAME_VERSION;
+    header[1] = algorithm_id(algorithm);
+    header[2..6].copy_from_slice(&epoch.to_be_bytes());
+    header[6..14].copy_from_slice(&counter.to_be_bytes());
+    header
+}
+
+/// Serializes a message as `header || iv || tag || ciphertext`.
+pub fn serialize(msg: &EncryptedMessage) -> Vec<u8> {
+    let tag_len = msg.tag.as_ref().map_or(0, |t| t.len());
+    let mut out = Vec::with_capacity(HEADER_LEN + msg.iv.len() + tag_len + msg.data.len());
+    out.extend_from_slice(&header_bytes(&msg.algorithm, msg.epoch, msg.counter));
+    out.extend_from_slice(&msg.iv);
+    if let Some(tag) = &msg.tag {
+        out.extend_from_slice(tag);
+    }
+    out.extend_from_slice(&msg.data);
+    out
+}
+
+/// Parses a frame produced by [`serialize`], validating the version byte and
+/// decoding the algorithm from the frame itself rather than any external config.
+pub fn parse(bytes: &[u8]) -> Result<EncryptedMessage> {
+    if bytes.len() < HEADER_LEN + IV_LEN + TAG_LEN {
+        return Err(anyhow::anyhow!("frame too short"));
+    }
+    if bytes[0] != FRAME_VERSION {
+        return Err(anyhow::anyhow!("unsupported frame version {}", bytes[0]));
+    }
+
+    let algorithm = algorithm_from_id(bytes[1])?;
+    let epoch = u32::from_be_bytes(bytes[2..6].try_into().unwrap());
+    let counter = u64::from_be_bytes(bytes[6..14].try_into().unwrap());
+    let iv = bytes[HEADER_LEN..HEADER_LEN + IV_LEN].to_vec();
+    let tag = Some(bytes[HEADER_LEN + IV_LEN..HEADER_LEN + IV_LEN + TAG_LEN].to_vec());
+    let data = bytes[HEADER_LEN + IV_LEN + TAG_LEN..].to_vec();
+
+    Ok(EncryptedMessage {
+        data,
+        iv,
+        tag,
+        timestamp: crate::current_timestamp(),
+        counter,
+        epoch,
+        algorithm,
+    })
+}
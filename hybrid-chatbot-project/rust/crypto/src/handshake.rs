@@ -0,0 +1,279 @@
+//! Noise-inspired mutual authentication handshake used to establish session
+//! keys for [`CryptoService`] instead of relying on an out-of-band shared key.
+
+use crate::challenge::ChallengeRole;
+use crate::CryptoService;
+use anyhow::Result;
+use hkdf::Hkdf;
+use rand_core::OsRng;
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::sync::Arc;
+use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey, StaticSecret};
+
+/// Wire format version for handshake messages.
+const HANDSHAKE_VERSION: u8 = 1;
+
+/// Static X25519 key pair plus the set of peer public keys this node trusts.
+///
+/// Two ways to build one: [`HandshakeConfig::from_shared_secret`] derives a
+/// deterministic key pair from a passphrase so every node holding the same
+/// passphrase ends up with the same pair and trusts only that common key, and
+/// [`HandshakeConfig::with_explicit_trust`] generates a random pair and trusts
+/// an explicit list of peer public keys.
+pub struct HandshakeConfig {
+    secret: StaticSecret,
+    public_key: X25519PublicKey,
+    trusted_keys: HashSet<[u8; 32]>,
+}
+
+impl HandshakeConfig {
+    /// Derives a deterministic X25519 key pair from a shared passphrase via
+    /// HKDF-SHA256. Every node sharing the passphrase derives the same pair,
+    /// and trusts that pair's own public key as its only trusted peer.
+    pub fn from_shared_secret(passphrase: &[u8]) -> Result<Self> {
+        let hk = Hkdf::<Sha256>::new(None, passphrase);
+        let mut scalar = [0u8; 32];
+        hk.expand(b"chatbot-handshake-keypair", &mut scalar)
+            .map_err(|_| anyhow::anyhow!("failed to derive key pair from passphrase"))?;
+
+        let secret = StaticSecret::from(scalar);
+        let public_key = X25519PublicKey::from(&secret);
+        let mut trusted_keys = HashSet::new();
+        trusted_keys.insert(public_key.to_bytes());
+
+        Ok(Self {
+            secret,
+            public_key,
+            trusted_keys,
+        })
+    }
+
+    /// Generates a random X25519 key pair and trusts only the given peer
+    /// public keys.
+    pub fn with_explicit_trust(trusted_peers: Vec<[u8; 32]>) -> Self {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let public_key = X25519PublicKey::from(&secret);
+
+        Self {
+            secret,
+            public_key,
+            trusted_keys: trusted_peers.into_iter().collect(),
+        }
+    }
+
+    /// This node's static public key, e.g. to hand to peers out of band so
+    /// they can add it to their own trusted set.
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public_key.to_bytes()
+    }
+}
+
+/// Which side of the handshake a [`HandshakeSession`] is playing.
+enum HandshakeRole {
+    Initiator,
+    Responder,
+}
+
+/// Result of feeding a message into [`HandshakeSession::process_handshake_message`].
+pub enum HandshakeStep {
+    /// Send these bytes to the peer; this side is not done yet.
+    Reply(Vec<u8>),
+    /// The peer is authenticated and the session is ready to use.
+    Complete(CryptoService),
+    /// Send these bytes to the peer, and this side is already done.
+    ReplyAndComplete(Vec<u8>, CryptoService),
+}
+
+/// Drives one run of the handshake protocol for either the initiating or the
+/// responding node.
+///
+/// The initiator sends its static public key and a fresh ephemeral public
+/// key. The responder checks the initiator's static key against its trusted
+/// set, derives the session key from `ephemeral×static` and `static×static`
+/// Diffie-Hellman results, and replies with its own static public key so the
+/// initiator can perform the same checks and derivation on its side.
+pub struct HandshakeSession {
+    config: Arc<HandshakeConfig>,
+    role: HandshakeRole,
+    ephemeral_secret: Option<EphemeralSecret>,
+}
+
+impl HandshakeSession {
+    /// Starts a handshake as the initiating node.
+    pub fn new_initiator(config: Arc<HandshakeConfig>) -> Self {
+        Self {
+            config,
+            role: HandshakeRole::Initiator,
+            ephemeral_secret: None,
+        }
+    }
+
+    /// Starts a handshake as the responding node.
+    pub fn new_responder(config: Arc<HandshakeConfig>) -> Self {
+        Self {
+            config,
+            role: HandshakeRole::Responder,
+            ephemeral_secret: None,
+        }
+    }
+
+    /// Initiator-only: produces the first handshake message to send to the peer.
+    pub fn begin_handshake(&mut self) -> Result<Vec<u8>> {
+        if !matches!(self.role, HandshakeRole::Initiator) {
+            return Err(anyhow::anyhow!("only the initiator can begin a handshake"));
+        }
+
+        let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral_public = X25519PublicKey::from(&ephemeral_secret);
+        self.ephemeral_secret = Some(ephemeral_secret);
+
+        let mut message = Vec::with_capacity(65);
+        message.push(HANDSHAKE_VERSION);
+        message.extend_from_slice(&self.config.public_key.to_bytes());
+        message.extend_from_slice(ephemeral_public.as_bytes());
+        Ok(message)
+    }
+
+    /// Consumes a message from the peer, advancing the handshake.
+    pub fn process_handshake_message(&mut self, message: &[u8]) -> Result<HandshakeStep> {
+        match self.role {
+            HandshakeRole::Responder => self.process_as_responder(message),
+            HandshakeRole::Initiator => self.process_as_initiator(message),
+        }
+    }
+
+    fn process_as_responder(&mut self, message: &[u8]) -> Result<HandshakeStep> {
+        let (peer_static, peer_ephemeral) = parse_initiator_message(message)?;
+        if !self.config.trusted_keys.contains(&peer_static) {
+            return Err(anyhow::anyhow!("peer static key is not in the trusted set"));
+        }
+
+        let dh_es = self
+            .config
+            .secret
+            .diffie_hellman(&X25519PublicKey::from(peer_ephemeral));
+        let dh_ss = self
+            .config
+            .secret
+            .diffie_hellman(&X25519PublicKey::from(peer_static));
+        let session_key = derive_session_key(dh_es.as_bytes(), dh_ss.as_bytes())?;
+
+        let mut reply = Vec::with_capacity(33);
+        reply.push(HANDSHAKE_VERSION);
+        reply.extend_from_slice(&self.config.public_key.to_bytes());
+
+        Ok(HandshakeStep::ReplyAndComplete(
+            reply,
+            CryptoService::from_session_key(session_key, ChallengeRole::Responder),
+        ))
+    }
+
+    fn process_as_initiator(&mut self, message: &[u8]) -> Result<HandshakeStep> {
+        let peer_static = parse_responder_message(message)?;
+        if !self.config.trusted_keys.contains(&peer_static) {
+            return Err(anyhow::anyhow!("peer static key is not in the trusted set"));
+        }
+
+        let ephemeral_secret = self
+            .ephemeral_secret
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("begin_handshake must be called before processing a reply"))?;
+
+        let peer_static_key = X25519PublicKey::from(peer_static);
+        let dh_es = ephemeral_secret.diffie_hellman(&peer_static_key);
+        let dh_ss = self.config.secret.diffie_hellman(&peer_static_key);
+        let session_key = derive_session_key(dh_es.as_bytes(), dh_ss.as_bytes())?;
+
+        Ok(HandshakeStep::Complete(CryptoService::from_session_key(
+            session_key,
+            ChallengeRole::Initiator,
+        )))
+    }
+}
+
+/// Derives a 32-byte session key from the concatenated DH outputs via HKDF-SHA256.
+fn derive_session_key(dh_es: &[u8], dh_ss: &[u8]) -> Result<[u8; 32]> {
+    let mut ikm = Vec::with_capacity(dh_es.len() + dh_ss.len());
+    ikm.extend_from_slice(dh_es);
+    ikm.extend_from_slice(dh_ss);
+
+    let hk = Hkdf::<Sha256>::new(None, &ikm);
+    let mut session_key = [0u8; 32];
+    hk.expand(b"chatbot-session-key", &mut session_key)
+        .map_err(|_| anyhow::anyhow!("failed to derive session key"))?;
+    Ok(session_key)
+}
+
+/// Parses `version(1) || static_pub(32) || ephemeral_pub(32)`.
+fn parse_initiator_message(message: &[u8]) -> Result<([u8; 32], [u8; 32])> {
+    if message.len() != 65 || message[0] != HANDSHAKE_VERSION {
+        return Err(anyhow::anyhow!("malformed handshake message"));
+    }
+    let mut static_key = [0u8; 32];
+    let mut ephemeral_key = [0u8; 32];
+    static_key.copy_from_slice(&message[1..33]);
+    ephemeral_key.copy_from_slice(&message[33..65]);
+    Ok((static_key, ephemeral_key))
+}
+
+/// Parses `version(1) || static_pub(32)`.
+fn parse_responder_message(message: &[u8]) -> Result<[u8; 32]> {
+    if message.len() != 33 || message[0] != HANDSHAKE_VERSION {
+        return Err(anyhow::anyhow!("malformed handshake message"));
+    }
+    let mut static_key = [0u8; 32];
+    static_key.copy_from_slice(&message[1..33]);
+    Ok(static_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Both sides of a passphrase-based deployment derive the same static
+    /// key pair from `from_shared_secret` and trust only that pair's own
+    /// public key, so a single passphrase is enough to drive a full
+    /// initiator/responder round trip here without exchanging keys out of
+    /// band first.
+    #[test]
+    fn handshake_round_trip_yields_usable_session_keys() {
+        let initiator_config = Arc::new(HandshakeConfig::from_shared_secret(b"shared passphrase").unwrap());
+        let responder_config = Arc::new(HandshakeConfig::from_shared_secret(b"shared passphrase").unwrap());
+
+        let mut initiator = HandshakeSession::new_initiator(initiator_config);
+        let mut responder = HandshakeSession::new_responder(responder_config);
+
+        let first_message = initiator.begin_handshake().unwrap();
+
+        let (reply, responder_service) = match responder.process_handshake_message(&first_message).unwrap() {
+            HandshakeStep::ReplyAndComplete(reply, service) => (reply, service),
+            _ => panic!("responder should complete on the first message"),
+        };
+
+        let initiator_service = match initiator.process_handshake_message(&reply).unwrap() {
+            HandshakeStep::Complete(service) => service,
+            _ => panic!("initiator should complete on the responder's reply"),
+        };
+
+        // The two sides must have derived complementary, usable keys in both
+        // directions, not just matching opaque state.
+        let from_initiator = initiator_service.encrypt(b"hello responder").unwrap();
+        assert_eq!(responder_service.decrypt(&from_initiator).unwrap(), b"hello responder");
+
+        let from_responder = responder_service.encrypt(b"hello initiator").unwrap();
+        assert_eq!(initiator_service.decrypt(&from_responder).unwrap(), b"hello initiator");
+    }
+
+    #[test]
+    fn responder_rejects_untrusted_initiator() {
+        let initiator_config = Arc::new(HandshakeConfig::from_shared_secret(b"initiator passphrase").unwrap());
+        let responder_config = Arc::new(HandshakeConfig::from_shared_secret(b"different passphrase").unwrap());
+
+        let mut initiator = HandshakeSession::new_initiator(initiator_config);
+        let mut responder = HandshakeSession::new_responder(responder_config);
+
+        let first_message = initiator.begin_handshake().unwrap();
+        assert!(responder.process_handshake_message(&first_message).is_err());
+    }
+}
@@ -2,11 +2,24 @@
 //! Provides encryption/decryption, key management, and secure communication protocols
 
 use anyhow::Result;
+use hkdf::Hkdf;
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use sha2::Sha256;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use ring::{aead, aead::LessSafeKey, aead::UnboundKey, aead::Nonce, aead::Aad};
 
+mod challenge;
+mod frame;
+mod handshake;
+mod replay;
+mod tunnel;
+use challenge::ChallengeRole;
+use handshake::{HandshakeConfig, HandshakeSession, HandshakeStep};
+use replay::ReplayWindow;
+use tunnel::SecureTunnel;
+
 /// Represents an encrypted message with associated metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptedMessage {
@@ -18,6 +31,22 @@ pub struct EncryptedMessage {
     pub tag: Option<Vec<u8>>,
     /// Timestamp of encryption
     pub timestamp: u64,
+    /// Monotonic per-epoch message counter the nonce was derived from
+    pub counter: u64,
+    /// Rekey epoch the message was encrypted under
+    pub epoch: u32,
+    /// Algorithm the message was sealed with, read back on decrypt instead of
+    /// trusting the local `CryptoConfig` so a downgraded or mismatched
+    /// algorithm byte fails AAD verification rather than silently parsing
+    pub algorithm: CryptoAlgorithm,
+}
+
+/// Returns the current unix timestamp in seconds.
+fn current_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
 }
 
 /// Configuration for cryptographic operations
@@ -27,73 +56,276 @@ pub struct CryptoConfig {
     pub key: Vec<u8>,
     /// Algorithm to use
     pub algorithm: CryptoAlgorithm,
+    /// When to ratchet to a new session key
+    pub rekey_policy: RekeyPolicy,
 }
 
 /// Available cryptographic algorithms
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CryptoAlgorithm {
     Aes256Gcm,
     ChaCha20Poly1305,
 }
 
+/// Controls how often `CryptoService` ratchets to a fresh session key.
+#[derive(Debug, Clone)]
+pub struct RekeyPolicy {
+    /// Rekey after this many messages have been sent in the current epoch
+    pub max_messages: u64,
+    /// Rekey after this much time has elapsed in the current epoch
+    pub max_elapsed: Duration,
+}
+
+impl Default for RekeyPolicy {
+    fn default() -> Self {
+        Self {
+            max_messages: 1_000_000,
+            max_elapsed: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Per-direction state for one rekeying epoch of sending
+struct SendState {
+    key: Vec<u8>,
+    epoch: u32,
+    counter: u64,
+    messages_in_epoch: u64,
+    epoch_started_at: Instant,
+}
+
+/// Per-direction state for one rekeying epoch of receiving
+struct ReceiveState {
+    key: Vec<u8>,
+    epoch: u32,
+    window: ReplayWindow,
+}
+
+/// Specific `decrypt` rejection reasons that callers may need to tell apart
+/// from a genuine authentication failure — in particular, a transport should
+/// drop a peer's session on an auth failure, but not on a replay/stale-epoch
+/// rejection, since those are expected from reordering or duplicate delivery.
+#[derive(Debug)]
+pub enum DecryptError {
+    /// `encrypted.epoch` is older than the epoch this side has already ratcheted past.
+    StaleEpoch,
+    /// `encrypted.epoch` is further ahead than this side will ratchet in one call.
+    EpochTooFarAhead,
+    /// `encrypted.counter` was already seen or falls outside the replay window.
+    Replayed,
+}
+
+impl std::fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecryptError::StaleEpoch => write!(f, "message epoch is older than the current receive epoch"),
+            DecryptError::EpochTooFarAhead => {
+                write!(f, "message epoch is too far ahead of the current receive epoch")
+            }
+            DecryptError::Replayed => write!(f, "replayed or duplicate message counter"),
+        }
+    }
+}
+
+impl std::error::Error for DecryptError {}
+
+/// State of an in-progress HMAC challenge-response exchange
+#[derive(Default)]
+struct ChallengeState {
+    /// Nonce we issued, pending the peer's response
+    local_nonce: Option<[u8; 32]>,
+    /// Nonce the peer issued, that we already answered
+    peer_nonce: Option<[u8; 32]>,
+}
+
 /// Main cryptographic service
 pub struct CryptoService {
     config: Arc<CryptoConfig>,
+    send_state: Mutex<SendState>,
+    receive_state: Mutex<ReceiveState>,
+    challenge_state: Mutex<ChallengeState>,
 }
 
 impl CryptoService {
-    /// Creates a new cryptographic service with the given configuration
-    pub fn new(config: CryptoConfig) -> Self {
+    /// Creates a new cryptographic service with the given configuration.
+    /// `role` is this side's role in the pre-shared deployment: since
+    /// `encrypt` derives its nonce from a deterministic message counter
+    /// rather than randomness, both peers sealing under the same raw
+    /// `config.key` for both directions would reuse a (key, nonce) pair the
+    /// moment each side sent its first message. Instead two HKDF-labeled
+    /// directional keys are derived from `config.key` and assigned by
+    /// `role` via [`directional_keys`], exactly as `from_session_key` does
+    /// for the handshake-derived path; `config.key` itself is kept
+    /// unmodified for the HMAC challenge-response path.
+    pub fn new(config: CryptoConfig, role: ChallengeRole) -> Self {
+        let (send_key, receive_key) = directional_keys(&config.key, role);
+
+        let send_state = Mutex::new(SendState {
+            key: send_key,
+            epoch: 0,
+            counter: 0,
+            messages_in_epoch: 0,
+            epoch_started_at: Instant::now(),
+        });
+        let receive_state = Mutex::new(ReceiveState {
+            key: receive_key,
+            epoch: 0,
+            window: ReplayWindow::new(),
+        });
+
         Self {
             config: Arc::new(config),
+            send_state,
+            receive_state,
+            challenge_state: Mutex::new(ChallengeState::default()),
         }
     }
 
-    /// Encrypts data using the configured algorithm
+    /// Builds a service from a session key produced by a completed handshake.
+    /// `role` is this side's role in that handshake. Defaults to
+    /// AES-256-GCM; callers that negotiated a different algorithm should
+    /// build a `CryptoConfig` directly instead.
+    pub(crate) fn from_session_key(key: [u8; 32], role: ChallengeRole) -> Self {
+        let (send_key, receive_key) = directional_keys(&key, role);
+        Self::from_directional_keys(send_key, receive_key, CryptoAlgorithm::Aes256Gcm)
+    }
+
+    /// Builds a service with distinct send/receive keys, e.g. for the
+    /// per-direction keys `from_session_key` derives from a shared session key.
+    fn from_directional_keys(send_key: Vec<u8>, receive_key: Vec<u8>, algorithm: CryptoAlgorithm) -> Self {
+        let send_state = Mutex::new(SendState {
+            key: send_key,
+            epoch: 0,
+            counter: 0,
+            messages_in_epoch: 0,
+            epoch_started_at: Instant::now(),
+        });
+        let receive_state = Mutex::new(ReceiveState {
+            key: receive_key,
+            epoch: 0,
+            window: ReplayWindow::new(),
+        });
+
+        // `config.key` backs only the HMAC challenge-response path, which
+        // handshake-derived services don't use (the handshake already
+        // authenticated the peer), so it's left empty here rather than
+        // holding the shared session key.
+        Self {
+            config: Arc::new(CryptoConfig {
+                key: Vec::new(),
+                algorithm,
+                rekey_policy: RekeyPolicy::default(),
+            }),
+            send_state,
+            receive_state,
+            challenge_state: Mutex::new(ChallengeState::default()),
+        }
+    }
+
+    /// Encrypts data using the configured algorithm. The nonce is a
+    /// monotonically increasing message counter zero-extended into the
+    /// 12-byte AEAD nonce rather than random, so the receiver can use it for
+    /// replay detection; the key is ratcheted forward automatically once the
+    /// configured rekey policy is exceeded.
     pub fn encrypt(&self, data: &[u8]) -> Result<EncryptedMessage> {
-        let key_bytes = &self.config.key;
+        let mut state = self.send_state.lock().unwrap();
+
+        if state.messages_in_epoch >= self.config.rekey_policy.max_messages
+            || state.epoch_started_at.elapsed() >= self.config.rekey_policy.max_elapsed
+        {
+            state.key = ratchet_key(&state.key)?;
+            state.epoch += 1;
+            state.counter = 0;
+            state.messages_in_epoch = 0;
+            state.epoch_started_at = Instant::now();
+        }
+
+        state.counter += 1;
+        let counter = state.counter;
+        let epoch = state.epoch;
+        state.messages_in_epoch += 1;
+
         let alg = match self.config.algorithm {
             CryptoAlgorithm::Aes256Gcm => &aead::AES_256_GCM,
             CryptoAlgorithm::ChaCha20Poly1305 => &aead::CHACHA20_POLY1305,
         };
-        
-        let unbound_key = UnboundKey::new(alg, key_bytes)?;
+
+        let unbound_key = UnboundKey::new(alg, &state.key)?;
         let key = LessSafeKey::new(unbound_key);
-        
-        // Generate random nonce (IV)
-        let mut nonce_bytes = [0u8; 12];  // 96-bit nonce for AES-GCM
-        get_random_bytes(&mut nonce_bytes);
+
+        let nonce_bytes = nonce_from_counter(counter);
         let nonce = Nonce::try_assume_unique_for_key(nonce_bytes)?;
-        
-        // Create additional authenticated data (AAD)
-        let aad = Aad::from(&[]);
-        
+
+        // Bind the frame header (version, algorithm, epoch, counter) as AAD so
+        // tampering with or downgrading it fails authentication.
+        let header = frame::header_bytes(&self.config.algorithm, epoch, counter);
+        let aad = Aad::from(&header);
+
         // Prepare buffer for encryption (data + tag)
         let mut in_out = data.to_vec();
         let tag = key.seal_in_place_append_tag(nonce, aad, &mut in_out)?;
-        
+
         Ok(EncryptedMessage {
             data: in_out,
             iv: nonce_bytes.to_vec(),
             tag: Some(tag.as_ref().to_vec()),
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: current_timestamp(),
+            counter,
+            epoch,
+            algorithm: self.config.algorithm.clone(),
         })
     }
 
-    /// Decrypts data using the configured algorithm
+    /// Decrypts data using the configured algorithm, rejecting replayed or
+    /// duplicate counters and advancing the receive key if the message
+    /// signals a later rekey epoch than the one we're tracking.
+    ///
+    /// `epoch`/`counter` arrive unauthenticated, so none of the receive
+    /// state is mutated on their say-so alone: the epoch ratchet and replay
+    /// window are computed into local candidates first, and only written
+    /// back to `state` after `open_in_place` has verified the AEAD tag.
+    /// Ratcheting is also capped at `MAX_EPOCH_ADVANCE` per call, so a
+    /// forged `epoch` field can't force thousands of HKDF expansions before
+    /// authentication even runs.
     pub fn decrypt(&self, encrypted: &EncryptedMessage) -> Result<Vec<u8>> {
-        let key_bytes = &self.config.key;
-        let alg = match self.config.algorithm {
+        let mut state = self.receive_state.lock().unwrap();
+
+        if encrypted.epoch < state.epoch {
+            return Err(DecryptError::StaleEpoch.into());
+        }
+        let epoch_advance = u64::from(encrypted.epoch) - u64::from(state.epoch);
+        if epoch_advance > MAX_EPOCH_ADVANCE {
+            return Err(DecryptError::EpochTooFarAhead.into());
+        }
+
+        // Candidate key/window for the message's claimed epoch, computed
+        // without touching `state` yet.
+        let mut candidate_key = state.key.clone();
+        for _ in 0..epoch_advance {
+            candidate_key = ratchet_key(&candidate_key)?;
+        }
+        let mut candidate_window = if epoch_advance > 0 {
+            ReplayWindow::new()
+        } else {
+            state.window.clone()
+        };
+
+        if !candidate_window.check_and_update(encrypted.counter) {
+            return Err(DecryptError::Replayed.into());
+        }
+
+        // The algorithm comes from the message itself, not `CryptoConfig`, so
+        // a frame claiming a different cipher than we negotiated is decoded
+        // with that cipher and then rejected by AAD verification rather than
+        // silently decrypted with the wrong one.
+        let alg = match encrypted.algorithm {
             CryptoAlgorithm::Aes256Gcm => &aead::AES_256_GCM,
             CryptoAlgorithm::ChaCha20Poly1305 => &aead::CHACHA20_POLY1305,
         };
-        
-        let unbound_key = UnboundKey::new(alg, key_bytes)?;
+
+        let unbound_key = UnboundKey::new(alg, &candidate_key)?;
         let key = LessSafeKey::new(unbound_key);
-        
+
         // Reconstruct nonce from IV
         let mut nonce_bytes = [0u8; 12];
         if encrypted.iv.len() != 12 {
@@ -101,18 +333,170 @@ impl CryptoService {
         }
         nonce_bytes.copy_from_slice(&encrypted.iv);
         let nonce = Nonce::try_assume_unique_for_key(nonce_bytes)?;
-        
-        // Create additional authenticated data (AAD)
-        let aad = Aad::from(&[]);
-        
+
+        // Bind the same header bytes used at encryption time as AAD
+        let header = frame::header_bytes(&encrypted.algorithm, encrypted.epoch, encrypted.counter);
+        let aad = Aad::from(&header);
+
         // Prepare buffer for decryption (data + tag)
         let mut in_out = encrypted.data.clone();
-        
-        // Decrypt and verify
+
+        // Decrypt and verify. Only once this succeeds do we know `epoch` and
+        // `counter` were authentic, so only now is it safe to commit the
+        // candidate epoch/key/window to the live receive state.
         let plaintext = key.open_in_place(nonce, aad, &mut in_out)?;
-        
+
+        state.key = candidate_key;
+        state.epoch = encrypted.epoch;
+        state.window = candidate_window;
+
         Ok(plaintext.to_vec())
     }
+
+    /// Issues a fresh random challenge nonce to send to the peer.
+    pub fn issue_challenge(&self) -> Vec<u8> {
+        let mut nonce = [0u8; 32];
+        get_random_bytes(&mut nonce);
+        self.challenge_state.lock().unwrap().local_nonce = Some(nonce);
+        nonce.to_vec()
+    }
+
+    /// Answers a challenge nonce issued by the peer, tagging the response
+    /// with `role` (our own role in the exchange) to prevent it being
+    /// reflected back as a different side's answer.
+    pub fn answer_challenge(&self, peer_nonce: &[u8], role: ChallengeRole) -> Result<Vec<u8>> {
+        if peer_nonce.len() != 32 {
+            return Err(anyhow::anyhow!("challenge nonce must be 32 bytes"));
+        }
+        let mut nonce = [0u8; 32];
+        nonce.copy_from_slice(peer_nonce);
+        self.challenge_state.lock().unwrap().peer_nonce = Some(nonce);
+
+        Ok(challenge::compute_response(&self.config.key, peer_nonce, role))
+    }
+
+    /// Verifies the peer's response to a challenge we issued, in constant
+    /// time. `role` is the peer's role in the exchange. As soon as we also
+    /// know the peer's issued nonce (from an earlier `answer_challenge`
+    /// call), the session key is ratcheted to mix in both nonces, so even a
+    /// long-lived pre-shared key yields fresh key material per session.
+    pub fn verify_response(&self, response: &[u8], role: ChallengeRole) -> Result<bool> {
+        let (local_nonce, peer_nonce) = {
+            let state = self.challenge_state.lock().unwrap();
+            let local_nonce = state
+                .local_nonce
+                .ok_or_else(|| anyhow::anyhow!("no challenge was issued"))?;
+            (local_nonce, state.peer_nonce)
+        };
+
+        let expected = challenge::compute_response(&self.config.key, &local_nonce, role);
+        if ring::constant_time::verify_slices_are_equal(&expected, response).is_err() {
+            return Ok(false);
+        }
+
+        if let Some(peer_nonce) = peer_nonce {
+            // `role` here is the peer's role (per this method's contract), so
+            // our own role for directional key derivation is its opposite.
+            self.rekey_from_challenge_nonces(&local_nonce, &peer_nonce, role.opposite())?;
+        }
+        Ok(true)
+    }
+
+    /// Mixes both challenge nonces into the pre-shared key via HKDF-SHA256,
+    /// then splits the result into per-direction send/receive keys for
+    /// `role` (our own role), exactly as `from_session_key` does for the
+    /// handshake-derived path — installing one shared `session_key` in both
+    /// directions would let both peers' counter nonces collide.
+    ///
+    /// `nonce_a`/`nonce_b` are passed in local-then-peer order by the caller,
+    /// which is swapped between the two sides of the exchange: one side
+    /// would otherwise expand HKDF with `local || peer` while the other
+    /// expands with `peer || local`, deriving different keys and desyncing.
+    /// Sorting the two nonces into a byte-order-independent order before
+    /// building `info` ensures both sides derive identical key material.
+    fn rekey_from_challenge_nonces(
+        &self,
+        nonce_a: &[u8; 32],
+        nonce_b: &[u8; 32],
+        role: ChallengeRole,
+    ) -> Result<()> {
+        let (first, second) = if nonce_a <= nonce_b {
+            (nonce_a, nonce_b)
+        } else {
+            (nonce_b, nonce_a)
+        };
+
+        let hk = Hkdf::<Sha256>::new(None, &self.config.key);
+        let mut info = Vec::with_capacity(64);
+        info.extend_from_slice(first);
+        info.extend_from_slice(second);
+        let mut session_key = [0u8; 32];
+        hk.expand(&info, &mut session_key)
+            .map_err(|_| anyhow::anyhow!("failed to derive session key from challenge nonces"))?;
+
+        let (send_key, receive_key) = directional_keys(&session_key, role);
+
+        let mut send_state = self.send_state.lock().unwrap();
+        send_state.key = send_key;
+        send_state.epoch = 0;
+        send_state.counter = 0;
+        send_state.messages_in_epoch = 0;
+        send_state.epoch_started_at = Instant::now();
+        drop(send_state);
+
+        let mut receive_state = self.receive_state.lock().unwrap();
+        receive_state.key = receive_key;
+        receive_state.epoch = 0;
+        receive_state.window = ReplayWindow::new();
+
+        Ok(())
+    }
+}
+
+/// Largest number of epochs `decrypt` will ratchet through for a single
+/// message. Bounds the cost of a forged `epoch` field before the AEAD tag
+/// has even been checked; legitimate peers never fall this far behind.
+const MAX_EPOCH_ADVANCE: u64 = 1024;
+
+/// Builds the 12-byte AEAD nonce by zero-extending the 64-bit message counter.
+fn nonce_from_counter(counter: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[4..12].copy_from_slice(&counter.to_be_bytes());
+    nonce
+}
+
+/// Derives a directional sub-key from a shared `key` via HKDF-SHA256,
+/// labeled with `direction` (e.g. `b"initiator-to-responder"`) so the two
+/// directions of a session never share key material.
+fn derive_directional_key(key: &[u8], direction: &[u8]) -> Vec<u8> {
+    let hk = Hkdf::<Sha256>::new(None, key);
+    let mut derived = vec![0u8; 32];
+    hk.expand(direction, &mut derived)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    derived
+}
+
+/// Splits a shared `key` into a `(send_key, receive_key)` pair for `role`,
+/// via [`derive_directional_key`]: the initiator's send key is the
+/// responder's receive key and vice versa, so each direction gets its own
+/// counter-nonce space instead of both peers sealing under the same raw key.
+fn directional_keys(key: &[u8], role: ChallengeRole) -> (Vec<u8>, Vec<u8>) {
+    let initiator_to_responder = derive_directional_key(key, b"initiator-to-responder");
+    let responder_to_initiator = derive_directional_key(key, b"responder-to-initiator");
+
+    match role {
+        ChallengeRole::Initiator => (initiator_to_responder, responder_to_initiator),
+        ChallengeRole::Responder => (responder_to_initiator, initiator_to_responder),
+    }
+}
+
+/// Ratchets a session key forward via HKDF-SHA256 for automatic rekeying.
+fn ratchet_key(current: &[u8]) -> Result<Vec<u8>> {
+    let hk = Hkdf::<Sha256>::new(None, current);
+    let mut next = vec![0u8; current.len()];
+    hk.expand(b"chatbot-rekey", &mut next)
+        .map_err(|_| anyhow::anyhow!("failed to ratchet session key"))?;
+    Ok(next)
 }
 
 /// Helper function to generate random bytes
@@ -122,6 +506,17 @@ fn get_random_bytes(bytes: &mut [u8]) {
     rng.fill(bytes).unwrap();
 }
 
+/// Parses the `"initiator"`/`"responder"` role strings used at the Python boundary.
+fn parse_challenge_role(role: &str) -> PyResult<ChallengeRole> {
+    match role {
+        "initiator" => Ok(ChallengeRole::Initiator),
+        "responder" => Ok(ChallengeRole::Responder),
+        other => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "invalid role '{other}', expected 'initiator' or 'responder'"
+        ))),
+    }
+}
+
 /// Python bindings for the cryptographic service
 #[pymodule]
 fn crypto(_py: Python, m: &PyModule) -> PyResult<()> {
@@ -132,15 +527,20 @@ fn crypto(_py: Python, m: &PyModule) -> PyResult<()> {
 
     #[pymethods]
     impl PyCryptoService {
+        /// `role` is `"initiator"` or `"responder"` and identifies this
+        /// side's role in the pre-shared deployment, so the two peers derive
+        /// distinct send/receive keys instead of reusing one key in both
+        /// directions. The two peers sharing `key` MUST pass opposite roles.
         #[new]
-        fn new(key: Vec<u8>) -> Self {
+        fn new(key: Vec<u8>, role: String) -> PyResult<Self> {
             let config = CryptoConfig {
                 key,
                 algorithm: CryptoAlgorithm::Aes256Gcm,
+                rekey_policy: RekeyPolicy::default(),
             };
-            let service = Arc::new(CryptoService::new(config));
-            
-            Self { service }
+            let service = Arc::new(CryptoService::new(config, parse_challenge_role(&role)?));
+
+            Ok(Self { service })
         }
 
         fn encrypt(&self, data: Vec<u8>) -> PyResult<Vec<u8>> {
@@ -148,47 +548,227 @@ fn crypto(_py: Python, m: &PyModule) -> PyResult<()> {
                 .service
                 .encrypt(&data)
                 .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?;
-            
-            // Serialize the EncryptedMessage to bytes for Python
-            let mut result = Vec::new();
-            result.extend_from_slice(&encrypted.iv);
-            if let Some(tag) = &encrypted.tag {
-                result.extend_from_slice(tag);
-            }
-            result.extend_from_slice(&encrypted.data);
-            
-            Ok(result)
+
+            Ok(frame::serialize(&encrypted))
         }
 
         fn decrypt(&self, data: Vec<u8>) -> PyResult<Vec<u8>> {
-            // Parse the encrypted data: IV (12 bytes) + Tag (16 bytes for AES-GCM) + ciphertext
-            if data.len() < 28 {  // At least IV + tag
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Encrypted data too short"));
-            }
-            
-            let iv = data[0..12].to_vec();
-            let tag = Some(data[12..28].to_vec());
-            let ciphertext = data[28..].to_vec();
-            
-            let encrypted_msg = EncryptedMessage {
-                data: ciphertext,
-                iv,
-                tag,
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-            };
-            
+            let encrypted_msg = frame::parse(&data)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+
             let decrypted = self
                 .service
                 .decrypt(&encrypted_msg)
                 .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?;
-            
+
             Ok(decrypted)
         }
+
+        /// Issues a fresh challenge nonce to send to the peer.
+        fn issue_challenge(&self) -> Vec<u8> {
+            self.service.issue_challenge()
+        }
+
+        /// Answers a challenge nonce issued by the peer. `role` is `"initiator"`
+        /// or `"responder"` and identifies our own role in the exchange.
+        fn answer_challenge(&self, peer_nonce: Vec<u8>, role: String) -> PyResult<Vec<u8>> {
+            self.service
+                .answer_challenge(&peer_nonce, parse_challenge_role(&role)?)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))
+        }
+
+        /// Verifies the peer's response to a challenge we issued. `role` is
+        /// `"initiator"` or `"responder"` and identifies the peer's role.
+        fn verify_response(&self, response: Vec<u8>, role: String) -> PyResult<bool> {
+            self.service
+                .verify_response(&response, parse_challenge_role(&role)?)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))
+        }
+    }
+
+    /// Python bindings for a node's X25519 key pair and trusted peer set
+    #[pyclass]
+    struct PyHandshakeConfig {
+        config: Arc<HandshakeConfig>,
+    }
+
+    #[pymethods]
+    impl PyHandshakeConfig {
+        /// Derives a key pair from a shared passphrase; the node trusts only
+        /// its own derived public key, so every node sharing the passphrase
+        /// mutually trusts the others.
+        #[staticmethod]
+        fn from_shared_secret(passphrase: Vec<u8>) -> PyResult<Self> {
+            let config = HandshakeConfig::from_shared_secret(&passphrase)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?;
+            Ok(Self {
+                config: Arc::new(config),
+            })
+        }
+
+        /// Generates a random key pair and trusts only the given peer public keys.
+        #[staticmethod]
+        fn with_explicit_trust(trusted_peers: Vec<Vec<u8>>) -> PyResult<Self> {
+            let mut peers = Vec::with_capacity(trusted_peers.len());
+            for peer in trusted_peers {
+                if peer.len() != 32 {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "trusted peer keys must be 32 bytes",
+                    ));
+                }
+                let mut key = [0u8; 32];
+                key.copy_from_slice(&peer);
+                peers.push(key);
+            }
+            Ok(Self {
+                config: Arc::new(HandshakeConfig::with_explicit_trust(peers)),
+            })
+        }
+
+        fn public_key(&self) -> Vec<u8> {
+            self.config.public_key().to_vec()
+        }
+    }
+
+    /// Python bindings for an in-progress handshake
+    #[pyclass]
+    struct PyHandshakeSession {
+        session: HandshakeSession,
+    }
+
+    #[pymethods]
+    impl PyHandshakeSession {
+        #[staticmethod]
+        fn new_initiator(config: &PyHandshakeConfig) -> Self {
+            Self {
+                session: HandshakeSession::new_initiator(config.config.clone()),
+            }
+        }
+
+        #[staticmethod]
+        fn new_responder(config: &PyHandshakeConfig) -> Self {
+            Self {
+                session: HandshakeSession::new_responder(config.config.clone()),
+            }
+        }
+
+        fn begin_handshake(&mut self) -> PyResult<Vec<u8>> {
+            self.session
+                .begin_handshake()
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))
+        }
+
+        /// Returns `(reply_bytes, crypto_service)`; either may be `None`
+        /// depending on how far the handshake has progressed.
+        fn process_handshake_message(
+            &mut self,
+            message: Vec<u8>,
+        ) -> PyResult<(Option<Vec<u8>>, Option<PyCryptoService>)> {
+            let step = self
+                .session
+                .process_handshake_message(&message)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?;
+
+            Ok(match step {
+                HandshakeStep::Reply(bytes) => (Some(bytes), None),
+                HandshakeStep::Complete(service) => (
+                    None,
+                    Some(PyCryptoService {
+                        service: Arc::new(service),
+                    }),
+                ),
+                HandshakeStep::ReplyAndComplete(bytes, service) => (
+                    Some(bytes),
+                    Some(PyCryptoService {
+                        service: Arc::new(service),
+                    }),
+                ),
+            })
+        }
+    }
+
+    /// Python bindings for an encrypted, authenticated UDP tunnel
+    #[pyclass]
+    struct PySecureTunnel {
+        tunnel: SecureTunnel,
+    }
+
+    #[pymethods]
+    impl PySecureTunnel {
+        /// Binds a UDP socket (e.g. `"0.0.0.0:9000"`) and starts the
+        /// background receive loop.
+        #[new]
+        fn new(bind_addr: String, config: &PyHandshakeConfig) -> PyResult<Self> {
+            let tunnel = SecureTunnel::bind(&bind_addr, config.config.clone())
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?;
+            Ok(Self { tunnel })
+        }
+
+        /// Sends `data` to `peer_addr` (e.g. `"127.0.0.1:9001"`), handshaking
+        /// with that peer first on first contact.
+        fn send(&self, peer_addr: String, data: Vec<u8>) -> PyResult<()> {
+            let peer: std::net::SocketAddr = peer_addr
+                .parse()
+                .map_err(|_| PyErr::new::<pyo3::exceptions::PyValueError, _>("invalid peer address"))?;
+            self.tunnel
+                .send(peer, &data)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))
+        }
+
+        /// Blocks until a decrypted datagram arrives, returning `(peer_addr, data)`.
+        fn recv(&self) -> PyResult<(String, Vec<u8>)> {
+            let (peer, plaintext) = self
+                .tunnel
+                .recv()
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?;
+            Ok((peer.to_string(), plaintext))
+        }
     }
 
     m.add_class::<PyCryptoService>()?;
+    m.add_class::<PyHandshakeConfig>()?;
+    m.add_class::<PyHandshakeSession>()?;
+    m.add_class::<PySecureTunnel>()?;
     Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paired_services() -> (CryptoService, CryptoService) {
+        let config = CryptoConfig {
+            key: vec![0x42; 32],
+            algorithm: CryptoAlgorithm::Aes256Gcm,
+            rekey_policy: RekeyPolicy::default(),
+        };
+        let sender = CryptoService::new(config.clone(), ChallengeRole::Initiator);
+        let receiver = CryptoService::new(config, ChallengeRole::Responder);
+        (sender, receiver)
+    }
+
+    #[test]
+    fn round_trip_without_tampering_succeeds() {
+        let (sender, receiver) = paired_services();
+        let encrypted = sender.encrypt(b"hello").unwrap();
+        assert_eq!(receiver.decrypt(&encrypted).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn tampering_with_the_serialized_header_fails_decryption() {
+        let (sender, receiver) = paired_services();
+        let encrypted = sender.encrypt(b"hello").unwrap();
+
+        let mut framed = frame::serialize(&encrypted);
+        // Byte 13 is the last byte of the counter field (version(1) +
+        // algorithm(1) + epoch(4) precede the 8-byte counter). Flipping it
+        // turns counter 1 into 254, which the replay window accepts, while
+        // the epoch, key, IV, ciphertext and tag are all untouched; only the
+        // AAD the header is bound under changes, so this proves the header
+        // is authenticated rather than just framed.
+        framed[13] ^= 0xFF;
+
+        let tampered = frame::parse(&framed).unwrap();
+        assert!(receiver.decrypt(&tampered).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,110 @@
+//! Sliding-window replay detection for sequence-numbered messages.
+
+/// Tracks the highest message counter seen plus a bitmap of recently-seen
+/// counters within the window, so that out-of-order and lost messages are
+/// tolerated while replayed or duplicate counters are rejected.
+#[derive(Clone)]
+pub struct ReplayWindow {
+    highest: u64,
+    // Bit 0 tracks `highest`, bit N tracks `highest - N`.
+    bitmap: u64,
+}
+
+impl ReplayWindow {
+    /// Starts a fresh window; no counters have been seen yet.
+    pub fn new() -> Self {
+        Self {
+            highest: 0,
+            bitmap: 0,
+        }
+    }
+
+    /// Checks whether `counter` is acceptable (ahead of the window, or
+    /// in-window and not yet seen) and records it. Returns `false` for a
+    /// replay, a duplicate, or a counter older than the window can track.
+    pub fn check_and_update(&mut self, counter: u64) -> bool {
+        if counter > self.highest {
+            let shift = counter - self.highest;
+            self.bitmap = if shift >= 64 { 0 } else { self.bitmap << shift };
+            self.bitmap |= 1;
+            self.highest = counter;
+            return true;
+        }
+
+        let age = self.highest - counter;
+        if age >= 64 {
+            return false;
+        }
+
+        let bit = 1u64 << age;
+        if self.bitmap & bit != 0 {
+            return false;
+        }
+        self.bitmap |= bit;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_order_counters_are_accepted() {
+        let mut window = ReplayWindow::new();
+        assert!(window.check_and_update(1));
+        assert!(window.check_and_update(2));
+        assert!(window.check_and_update(3));
+    }
+
+    #[test]
+    fn duplicate_counter_is_rejected() {
+        let mut window = ReplayWindow::new();
+        assert!(window.check_and_update(5));
+        assert!(!window.check_and_update(5));
+    }
+
+    #[test]
+    fn reordered_in_window_counter_is_accepted_once() {
+        let mut window = ReplayWindow::new();
+        assert!(window.check_and_update(10));
+        // 7 is behind the current highest (10) but still within the window.
+        assert!(window.check_and_update(7));
+        // Replaying it is rejected.
+        assert!(!window.check_and_update(7));
+    }
+
+    #[test]
+    fn counter_older_than_the_window_is_rejected() {
+        let mut window = ReplayWindow::new();
+        assert!(window.check_and_update(1000));
+        // 64 or more behind `highest` falls outside the bitmap entirely.
+        assert!(!window.check_and_update(1000 - 64));
+        assert!(!window.check_and_update(1));
+    }
+
+    #[test]
+    fn window_shifts_forward_as_new_counters_arrive() {
+        let mut window = ReplayWindow::new();
+        for counter in 1..=70 {
+            assert!(window.check_and_update(counter));
+        }
+        // The window has shifted past the earliest counters, so they're now
+        // indistinguishable from a replay of something outside the window.
+        assert!(!window.check_and_update(1));
+        // A counter still within the shifted window is still tracked.
+        assert!(!window.check_and_update(70));
+        assert!(window.check_and_update(71));
+    }
+
+    #[test]
+    fn large_forward_jump_resets_the_bitmap() {
+        let mut window = ReplayWindow::new();
+        assert!(window.check_and_update(1));
+        // Jumping forward by more than the window width shouldn't leave
+        // stale bits around that could wrongly reject the new neighborhood.
+        assert!(window.check_and_update(1_000_000));
+        assert!(window.check_and_update(1_000_000 - 1));
+        assert!(!window.check_and_update(1_000_000));
+    }
+}
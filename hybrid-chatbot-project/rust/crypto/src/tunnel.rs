@@ -0,0 +1,271 @@
+//! Encrypted UDP transport built on [`CryptoService`] and the handshake
+//! subsystem, so callers can ship plaintext between chatbot nodes without
+//! reimplementing framing, peer management, or the handshake themselves.
+
+use crate::frame;
+use crate::handshake::{HandshakeConfig, HandshakeSession, HandshakeStep};
+use crate::{CryptoService, DecryptError};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const PACKET_HANDSHAKE: u8 = 0;
+const PACKET_DATA: u8 = 1;
+
+/// How long `send` waits for a first-contact handshake to complete before
+/// giving up.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Per-peer session state: either an in-progress handshake or an established
+/// `CryptoService` ready to encrypt/decrypt traffic for that peer.
+enum PeerState {
+    Handshaking(HandshakeSession),
+    Established(EstablishedPeer),
+}
+
+/// An established session plus how many AEAD authentication failures it has
+/// seen in a row. A single forged or corrupt datagram spoofing a peer's
+/// (trivially forged) UDP source address is indistinguishable from one
+/// authentication failure, so the session is only torn down once failures
+/// have piled up past `MAX_CONSECUTIVE_AUTH_FAILURES` rather than on the
+/// first one.
+struct EstablishedPeer {
+    service: Arc<CryptoService>,
+    consecutive_auth_failures: u32,
+}
+
+impl EstablishedPeer {
+    fn new(service: Arc<CryptoService>) -> Self {
+        Self { service, consecutive_auth_failures: 0 }
+    }
+}
+
+/// How many consecutive AEAD authentication failures (not replay/epoch
+/// rejections, which never count) an established session tolerates before
+/// it's dropped and the next `send` is forced to re-handshake.
+const MAX_CONSECUTIVE_AUTH_FAILURES: u32 = 5;
+
+/// An authenticated, encrypted datagram tunnel to any number of peers,
+/// keyed by socket address. Each peer gets its own handshake state and
+/// send/receive counters via its own `CryptoService`.
+pub struct SecureTunnel {
+    socket: Arc<UdpSocket>,
+    handshake_config: Arc<HandshakeConfig>,
+    peers: Arc<Mutex<HashMap<SocketAddr, PeerState>>>,
+    inbox: Mutex<mpsc::Receiver<(SocketAddr, Vec<u8>)>>,
+}
+
+impl SecureTunnel {
+    /// Binds a UDP socket and starts the background receive loop.
+    pub fn bind(addr: &str, handshake_config: Arc<HandshakeConfig>) -> Result<Self> {
+        let socket = Arc::new(UdpSocket::bind(addr)?);
+        let peers: Arc<Mutex<HashMap<SocketAddr, PeerState>>> = Arc::new(Mutex::new(HashMap::new()));
+        let (sender, receiver) = mpsc::channel();
+
+        spawn_receive_loop(socket.clone(), handshake_config.clone(), peers.clone(), sender);
+
+        Ok(Self {
+            socket,
+            handshake_config,
+            peers,
+            inbox: Mutex::new(receiver),
+        })
+    }
+
+    /// Encrypts and sends `plaintext` to `peer`, performing the handshake
+    /// first if this is the first contact with that peer.
+    pub fn send(&self, peer: SocketAddr, plaintext: &[u8]) -> Result<()> {
+        let service = self.session_for(peer)?;
+        let encrypted = service.encrypt(plaintext)?;
+
+        let mut packet = Vec::with_capacity(1 + plaintext.len() + 48);
+        packet.push(PACKET_DATA);
+        packet.extend_from_slice(&frame::serialize(&encrypted));
+        self.socket.send_to(&packet, peer)?;
+        Ok(())
+    }
+
+    /// Blocks until a decrypted datagram from an authenticated peer arrives.
+    pub fn recv(&self) -> Result<(SocketAddr, Vec<u8>)> {
+        self.inbox
+            .lock()
+            .unwrap()
+            .recv()
+            .map_err(|_| anyhow::anyhow!("tunnel receive loop has stopped"))
+    }
+
+    /// Returns the established session for `peer`, initiating and waiting
+    /// out a handshake if none exists yet.
+    fn session_for(&self, peer: SocketAddr) -> Result<Arc<CryptoService>> {
+        if let Some(service) = self.established_session(peer) {
+            return Ok(service);
+        }
+
+        let mut session = HandshakeSession::new_initiator(self.handshake_config.clone());
+        let first_message = session.begin_handshake()?;
+
+        let mut packet = Vec::with_capacity(1 + first_message.len());
+        packet.push(PACKET_HANDSHAKE);
+        packet.extend_from_slice(&first_message);
+        self.socket.send_to(&packet, peer)?;
+
+        self.peers
+            .lock()
+            .unwrap()
+            .insert(peer, PeerState::Handshaking(session));
+
+        self.wait_for_session(peer)
+    }
+
+    fn established_session(&self, peer: SocketAddr) -> Option<Arc<CryptoService>> {
+        match self.peers.lock().unwrap().get(&peer) {
+            Some(PeerState::Established(established)) => Some(established.service.clone()),
+            _ => None,
+        }
+    }
+
+    fn wait_for_session(&self, peer: SocketAddr) -> Result<Arc<CryptoService>> {
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        loop {
+            if let Some(service) = self.established_session(peer) {
+                return Ok(service);
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow::anyhow!("handshake with {peer} timed out"));
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+/// Spawns the background thread that reads datagrams, drives the handshake
+/// state machine for unestablished peers, and forwards decrypted payloads.
+fn spawn_receive_loop(
+    socket: Arc<UdpSocket>,
+    handshake_config: Arc<HandshakeConfig>,
+    peers: Arc<Mutex<HashMap<SocketAddr, PeerState>>>,
+    inbox: mpsc::Sender<(SocketAddr, Vec<u8>)>,
+) {
+    thread::spawn(move || {
+        let mut buf = [0u8; 65536];
+        loop {
+            let (len, peer) = match socket.recv_from(&mut buf) {
+                Ok(result) => result,
+                Err(_) => continue,
+            };
+            if len == 0 {
+                continue;
+            }
+
+            match buf[0] {
+                PACKET_HANDSHAKE => {
+                    handle_handshake_packet(&socket, &handshake_config, &peers, peer, &buf[1..len])
+                }
+                PACKET_DATA => handle_data_packet(&peers, peer, &buf[1..len], &inbox),
+                _ => {} // unknown packet type: drop
+            }
+        }
+    });
+}
+
+fn handle_handshake_packet(
+    socket: &UdpSocket,
+    handshake_config: &Arc<HandshakeConfig>,
+    peers: &Arc<Mutex<HashMap<SocketAddr, PeerState>>>,
+    peer: SocketAddr,
+    message: &[u8],
+) {
+    let mut peers_guard = peers.lock().unwrap();
+
+    // Only take ownership of an in-progress handshake here; an already
+    // `Established` session is left in the map untouched unless and until
+    // this message actually drives a replacement handshake to completion,
+    // so a single spoofed or malformed handshake datagram for a peer's
+    // (trivially forged) address can't tear down a live session.
+    let mut session = match peers_guard.get(&peer) {
+        Some(PeerState::Handshaking(_)) => match peers_guard.remove(&peer) {
+            Some(PeerState::Handshaking(session)) => session,
+            _ => unreachable!("checked above"),
+        },
+        _ => HandshakeSession::new_responder(handshake_config.clone()),
+    };
+
+    match session.process_handshake_message(message) {
+        Ok(HandshakeStep::Reply(reply)) => {
+            send_handshake_reply(socket, peer, &reply);
+            peers_guard.insert(peer, PeerState::Handshaking(session));
+        }
+        Ok(HandshakeStep::Complete(service)) => {
+            peers_guard.insert(peer, PeerState::Established(EstablishedPeer::new(Arc::new(service))));
+        }
+        Ok(HandshakeStep::ReplyAndComplete(reply, service)) => {
+            send_handshake_reply(socket, peer, &reply);
+            peers_guard.insert(peer, PeerState::Established(EstablishedPeer::new(Arc::new(service))));
+        }
+        Err(_) => {
+            // Untrusted key or malformed message: drop it. Any existing
+            // `Established` session for this peer was never removed above,
+            // so it's left intact rather than torn down by this one bad
+            // datagram.
+        }
+    }
+}
+
+fn send_handshake_reply(socket: &UdpSocket, peer: SocketAddr, reply: &[u8]) {
+    let mut packet = Vec::with_capacity(1 + reply.len());
+    packet.push(PACKET_HANDSHAKE);
+    packet.extend_from_slice(reply);
+    let _ = socket.send_to(&packet, peer);
+}
+
+fn handle_data_packet(
+    peers: &Arc<Mutex<HashMap<SocketAddr, PeerState>>>,
+    peer: SocketAddr,
+    payload: &[u8],
+    inbox: &mpsc::Sender<(SocketAddr, Vec<u8>)>,
+) {
+    let service = match peers.lock().unwrap().get(&peer) {
+        Some(PeerState::Established(established)) => established.service.clone(),
+        _ => return, // no session with this peer yet: drop
+    };
+
+    let Ok(encrypted) = frame::parse(payload) else {
+        return;
+    };
+
+    match service.decrypt(&encrypted) {
+        Ok(plaintext) => {
+            if let Some(PeerState::Established(established)) = peers.lock().unwrap().get_mut(&peer) {
+                established.consecutive_auth_failures = 0;
+            }
+            let _ = inbox.send((peer, plaintext));
+        }
+        Err(err) if err.downcast_ref::<DecryptError>().is_some() => {
+            // A replayed/duplicate counter or a stale/too-far-ahead epoch is
+            // expected from network reordering or an attacker replaying a
+            // captured datagram, not evidence the session is broken. Drop
+            // the datagram but keep the session so legitimate traffic isn't
+            // forced to re-handshake.
+        }
+        Err(_) => {
+            // The AEAD tag itself didn't verify. That's consistent with the
+            // peer having restarted and rekeyed from scratch, but it's
+            // indistinguishable from a single forged or corrupt datagram
+            // spoofing the peer's address, so one bad datagram alone isn't
+            // enough to tear down the session: only once failures have piled
+            // up past the threshold is it dropped, forcing the next `send`
+            // to re-handshake.
+            let mut peers_guard = peers.lock().unwrap();
+            if let Some(PeerState::Established(established)) = peers_guard.get_mut(&peer) {
+                established.consecutive_auth_failures += 1;
+                if established.consecutive_auth_failures > MAX_CONSECUTIVE_AUTH_FAILURES {
+                    peers_guard.remove(&peer);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Background batching queue for `TensorRTEngine::infer_async`, coalescing
+//! concurrent single-request calls from multiple threads into batches
+//! (mirroring Apache Beam's TensorRT engine handler) and driving them
+//! through `TensorRTEngine::infer_batch` on a background worker thread.
+//!
+//! The worker submits each coalesced batch synchronously; it does not
+//! enqueue onto a CUDA stream or overlap copy with compute, since
+//! `TensorRTEngine::infer_batch` itself is a placeholder with no device
+//! work to overlap. A real implementation would enqueue each batch on its
+//! own stream instead of blocking the worker thread on it.
+
+use crate::TensorRTEngine;
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::Duration;
+
+struct SharedState {
+    output: Option<Result<Vec<f32>>>,
+    waker: Option<Waker>,
+}
+
+struct Job {
+    input: Vec<f32>,
+    state: Arc<Mutex<SharedState>>,
+}
+
+/// Resolves to the result of one inference request that was coalesced into
+/// a batch by a [`BatchingQueue`].
+pub struct InferFuture {
+    state: Arc<Mutex<SharedState>>,
+}
+
+impl Future for InferFuture {
+    type Output = Result<Vec<f32>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        match state.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// How long the worker waits for more requests to join a batch once at
+/// least one request is queued.
+const COALESCE_WINDOW: Duration = Duration::from_millis(5);
+
+/// Coalesces concurrent `infer_async` calls from multiple threads into
+/// batches of up to `max_batch_size`, driving them through
+/// `TensorRTEngine::infer_batch` on a background worker thread.
+pub struct BatchingQueue {
+    jobs: Arc<Mutex<VecDeque<Job>>>,
+    notify: Arc<Condvar>,
+}
+
+impl BatchingQueue {
+    /// Spawns the background batching worker for `engine`.
+    pub fn spawn(engine: Arc<TensorRTEngine>) -> Self {
+        let jobs: Arc<Mutex<VecDeque<Job>>> = Arc::new(Mutex::new(VecDeque::new()));
+        let notify = Arc::new(Condvar::new());
+
+        let worker_jobs = jobs.clone();
+        let worker_notify = notify.clone();
+        thread::spawn(move || run_worker(engine, worker_jobs, worker_notify));
+
+        Self { jobs, notify }
+    }
+
+    /// Submits `input` for inference, coalescing it with other pending
+    /// requests into the next batch. Resolves once that batch completes.
+    pub fn submit(&self, input: Vec<f32>) -> InferFuture {
+        let state = Arc::new(Mutex::new(SharedState { output: None, waker: None }));
+        self.jobs.lock().unwrap().push_back(Job { input, state: state.clone() });
+        self.notify.notify_one();
+        InferFuture { state }
+    }
+}
+
+fn run_worker(engine: Arc<TensorRTEngine>, jobs: Arc<Mutex<VecDeque<Job>>>, notify: Arc<Condvar>) {
+    loop {
+        let mut guard = jobs.lock().unwrap();
+        while guard.is_empty() {
+            guard = notify.wait(guard).unwrap();
+        }
+        drop(guard);
+        thread::sleep(COALESCE_WINDOW);
+
+        let mut guard = jobs.lock().unwrap();
+        let batch_size = guard.len().min(engine.max_batch_size().max(1));
+        let batch: Vec<Job> = guard.drain(..batch_size).collect();
+        drop(guard);
+
+        let inputs: Vec<Vec<f32>> = batch.iter().map(|job| job.input.clone()).collect();
+        match engine.infer_batch(&inputs) {
+            Ok(outputs) => {
+                for (job, output) in batch.into_iter().zip(outputs) {
+                    complete(job.state, Ok(output));
+                }
+            }
+            Err(e) => {
+                for job in batch {
+                    complete(job.state, Err(anyhow::anyhow!(e.to_string())));
+                }
+            }
+        }
+    }
+}
+
+fn complete(state: Arc<Mutex<SharedState>>, output: Result<Vec<f32>>) {
+    let mut state = state.lock().unwrap();
+    state.output = Some(output);
+    if let Some(waker) = state.waker.take() {
+        waker.wake();
+    }
+}
+
+struct ThreadWaker(thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Minimal single-threaded executor for driving an [`InferFuture`] to
+/// completion from synchronous callers (e.g. the Python bindings), which
+/// have no async runtime of their own to poll it.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = future;
+    // SAFETY: `future` is not moved again until it is dropped at the end of
+    // this function, so pinning it on the stack is sound.
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! INT8 entropy calibration (mirrors `IInt8EntropyCalibrator2`): streams
+//! representative input batches through the builder to compute per-tensor
+//! dynamic ranges, and caches them so later builds can skip recalibration.
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Streams calibration batches and produces (or reuses) a calibration cache.
+pub struct Int8Calibrator {
+    batch_size: usize,
+    cache_path: String,
+    // Placeholder per-tensor activation histograms; a real implementation
+    // would bucket activations collected while running each batch through
+    // the network, not just store the raw batch contents.
+    histograms: HashMap<String, Vec<f32>>,
+}
+
+impl Int8Calibrator {
+    /// Creates a calibrator that will read from / write to `cache_path`.
+    pub fn new(batch_size: usize, cache_path: String) -> Self {
+        Self {
+            batch_size,
+            cache_path,
+            histograms: HashMap::new(),
+        }
+    }
+
+    /// The batch size calibration batches are expected to be sized to.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Reads a previously written calibration cache, if one exists, so the
+    /// caller can skip recalibration entirely.
+    pub fn read_cache(&self) -> Option<Vec<u8>> {
+        std::fs::read(&self.cache_path).ok()
+    }
+
+    /// Feeds one batch of representative input data through entropy
+    /// calibration, updating the per-tensor activation histograms used to
+    /// compute dynamic ranges.
+    pub fn feed_batch(&mut self, batch: &[f32]) {
+        self.histograms
+            .entry("input".to_string())
+            .or_insert_with(Vec::new)
+            .extend_from_slice(batch);
+    }
+
+    /// Computes per-tensor dynamic ranges from the collected histograms
+    /// (entropy/KL-divergence minimization in a real implementation) and
+    /// writes them to the calibration cache file.
+    pub fn write_cache(&self) -> Result<()> {
+        let mut cache = Vec::new();
+        for (tensor, samples) in &self.histograms {
+            let min = samples.iter().cloned().fold(f32::INFINITY, f32::min);
+            let max = samples.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            cache.push(format!("{tensor}:{min}:{max}"));
+        }
+        std::fs::write(&self.cache_path, cache.join("\n"))?;
+        Ok(())
+    }
+}
@@ -4,7 +4,15 @@
 use anyhow::Result;
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+
+mod batch;
+mod calibration;
+mod onnx;
+mod version;
+use batch::{BatchingQueue, InferFuture};
+use calibration::Int8Calibrator;
+pub use version::{TensorRTVersion, VersionCheck};
 
 /// Configuration for TensorRT engine
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,10 +21,16 @@ pub struct TensorRTConfig {
     pub engine_path: String,
     /// Maximum batch size
     pub max_batch_size: usize,
-    /// Maximum workspace size in bytes
-    pub max_workspace_size: usize,
+    /// Per-pool device memory limits for the builder
+    pub memory_pools: MemoryPoolLimits,
     /// Data type precision
     pub precision: Precision,
+    /// Batch size used when streaming calibration data for `Precision::Int8`
+    pub calibration_batch_size: usize,
+    /// Builder optimization profiles for dynamic input shapes. Required by
+    /// [`TensorRTEngine::build_from_onnx`]; ignored when loading a
+    /// pre-built engine file.
+    pub profiles: Vec<OptimizationProfile>,
 }
 
 /// Available precision modes for TensorRT
@@ -24,14 +38,80 @@ pub struct TensorRTConfig {
 pub enum Precision {
     Float32,
     Float16,
+    Bf16,
     Int8,
 }
 
+/// Per-pool device memory limits for the TensorRT builder. These are `u64`
+/// rather than `usize`/`int` because workspace size alone can exceed 2GB on
+/// large models, which overflowed the 32-bit workspace size field TensorRT
+/// originally used.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MemoryPoolLimits {
+    /// General scratch workspace, in bytes.
+    pub workspace: u64,
+    /// DLA managed SRAM pool, in bytes.
+    pub dla_managed_sram: u64,
+    /// DLA local DRAM pool, in bytes.
+    pub dla_local_dram: u64,
+}
+
+impl Default for MemoryPoolLimits {
+    fn default() -> Self {
+        Self {
+            workspace: 1024 * 1024 * 1024, // 1GB
+            dla_managed_sram: 0,
+            dla_local_dram: 0,
+        }
+    }
+}
+
+/// A builder optimization profile for a dynamic-axis input tensor: the
+/// minimum, optimal (most common), and maximum dimensions TensorRT should
+/// plan for. A single engine can serve any shape between `min_dims` and
+/// `max_dims` at runtime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationProfile {
+    pub min_dims: Vec<usize>,
+    pub opt_dims: Vec<usize>,
+    pub max_dims: Vec<usize>,
+}
+
+impl OptimizationProfile {
+    /// Checks that `min_dims <= opt_dims <= max_dims` element-wise and that
+    /// all three share the same rank.
+    pub fn validate(&self) -> Result<()> {
+        if self.min_dims.len() != self.opt_dims.len() || self.opt_dims.len() != self.max_dims.len() {
+            anyhow::bail!("min_dims, opt_dims, and max_dims must have the same rank");
+        }
+        for i in 0..self.min_dims.len() {
+            if !(self.min_dims[i] <= self.opt_dims[i] && self.opt_dims[i] <= self.max_dims[i]) {
+                anyhow::bail!("dimension {i} must satisfy min <= opt <= max");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The TensorRT versions relevant to diagnosing version skew: the version
+/// this crate was linked against, the version actually loaded at runtime,
+/// and the version tag embedded in a specific engine plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionInfo {
+    pub linked: TensorRTVersion,
+    pub loaded: TensorRTVersion,
+    /// `None` if the plan has no parseable version tag, e.g. a genuine
+    /// TensorRT `.engine` plan built outside this crate.
+    pub engine: Option<TensorRTVersion>,
+}
+
 /// TensorRT engine wrapper
 pub struct TensorRTEngine {
     config: TensorRTConfig,
     /// Placeholder for actual TensorRT engine handle
     engine_handle: Option<u64>,
+    /// Lazily started on the first `infer_async` call.
+    batching_queue: OnceLock<BatchingQueue>,
 }
 
 impl TensorRTEngine {
@@ -41,6 +121,7 @@ impl TensorRTEngine {
         Ok(Self {
             engine_handle: None, // Placeholder
             config,
+            batching_queue: OnceLock::new(),
         })
     }
 
@@ -52,15 +133,112 @@ impl TensorRTEngine {
         Ok(output)
     }
 
-    /// Loads a pre-built TensorRT engine from file
+    /// Runs inference on up to `max_batch_size` inputs at once, packing them
+    /// into a single device buffer instead of issuing one enqueue per input.
+    pub fn infer_batch(&self, inputs: &[Vec<f32>]) -> Result<Vec<Vec<f32>>> {
+        if inputs.len() > self.config.max_batch_size {
+            anyhow::bail!(
+                "batch of {} exceeds max_batch_size {}",
+                inputs.len(),
+                self.config.max_batch_size
+            );
+        }
+        // Placeholder: a real implementation would copy all of `inputs` into
+        // one device buffer and issue a single enqueue for the whole batch.
+        inputs.iter().map(|input| self.infer(input)).collect()
+    }
+
+    /// Submits `input` to the background [`BatchingQueue`] (started lazily
+    /// on first use) so it can be coalesced with concurrent requests from
+    /// other threads into one batch, and returns a future that resolves
+    /// once that batch completes.
+    pub fn infer_async(self: &Arc<Self>, input: Vec<f32>) -> InferFuture {
+        self.batching_queue
+            .get_or_init(|| BatchingQueue::spawn(self.clone()))
+            .submit(input)
+    }
+
+    /// The configured maximum batch size, used by the batching queue to
+    /// size each coalesced batch.
+    pub fn max_batch_size(&self) -> usize {
+        self.config.max_batch_size
+    }
+
+    /// Loads a pre-built TensorRT engine from file, failing if the plan was
+    /// built with an incompatible (different major version) TensorRT, and
+    /// logging a warning if only the minor/patch version differs. Plans with
+    /// no parseable version tag (i.e. most real TensorRT `.engine` files)
+    /// load without a version check.
     pub fn load_from_file(path: &str) -> Result<Self> {
         let config = TensorRTConfig {
             engine_path: path.to_string(),
             max_batch_size: 1,
-            max_workspace_size: 1024 * 1024 * 1024, // 1GB
+            memory_pools: MemoryPoolLimits::default(),
             precision: Precision::Float16,
+            calibration_batch_size: 1,
+            profiles: Vec::new(),
         };
 
+        let engine = Self::new(config)?;
+        if let VersionCheck::VersionMismatch { engine: engine_version, loaded } =
+            engine.check_version_compatibility()?
+        {
+            eprintln!(
+                "warning: engine plan at {path} was built with TensorRT {engine_version}, \
+                 but {loaded} is loaded (minor/patch version skew)"
+            );
+        }
+        // `Compatible` needs no warning and `Unknown` (no version tag in the
+        // plan) isn't actionable, so both fall through to a successful load.
+        Ok(engine)
+    }
+
+    /// The linked, runtime-loaded, and engine-plan TensorRT versions, for
+    /// diagnosing "poor results" caused by silent version skew. `engine` is
+    /// `None` if the plan has no parseable version tag.
+    pub fn version_info(&self) -> Result<VersionInfo> {
+        let plan = std::fs::read(&self.config.engine_path)?;
+        Ok(VersionInfo {
+            linked: version::LINKED_VERSION,
+            loaded: version::loaded_version(),
+            engine: version::parse_header(&plan)?,
+        })
+    }
+
+    /// Compares this engine's serialized plan version against the
+    /// TensorRT version loaded at runtime. See [`version::check`].
+    pub fn check_version_compatibility(&self) -> Result<VersionCheck> {
+        let info = self.version_info()?;
+        version::check(info.engine, info.loaded)
+    }
+
+    /// Parses an ONNX model at `onnx_path` and builds a TensorRT engine from
+    /// it, mirroring `trtexec --onnx=... --saveEngine=...`. `config.profiles`
+    /// must contain at least one `OptimizationProfile` describing the range
+    /// of input shapes the engine should support.
+    pub fn build_from_onnx(onnx_path: &str, config: TensorRTConfig) -> Result<Self> {
+        onnx::build_engine(onnx_path, &config)?;
+        Self::new(config)
+    }
+
+    /// Builds a `Precision::Int8` engine, streaming `batches` of
+    /// representative input data through entropy calibration to compute
+    /// per-tensor dynamic ranges. If `calibration_cache_path` already holds a
+    /// cache from a previous run, recalibration is skipped entirely.
+    pub fn build_with_calibration(
+        config: TensorRTConfig,
+        batches: impl Iterator<Item = Vec<f32>>,
+        calibration_cache_path: String,
+    ) -> Result<Self> {
+        let mut calibrator = Int8Calibrator::new(config.calibration_batch_size, calibration_cache_path);
+
+        if calibrator.read_cache().is_none() {
+            for batch in batches {
+                calibrator.feed_batch(&batch);
+            }
+            calibrator.write_cache()?;
+        }
+
         Self::new(config)
     }
 }
@@ -77,15 +255,8 @@ fn tensorrt(_py: Python, m: &PyModule) -> PyResult<()> {
     impl PyTensorRTEngine {
         #[new]
         fn new(config: PyTensorRTConfig) -> PyResult<Self> {
-            let tensorrt_config = TensorRTConfig {
-                engine_path: config.engine_path,
-                max_batch_size: config.max_batch_size,
-                max_workspace_size: config.max_workspace_size,
-                precision: config.precision,
-            };
-
             let engine = Arc::new(
-                TensorRTEngine::new(tensorrt_config)
+                TensorRTEngine::new(config.to_core_config())
                     .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?,
             );
 
@@ -97,10 +268,42 @@ fn tensorrt(_py: Python, m: &PyModule) -> PyResult<()> {
                 .engine
                 .infer(&input)
                 .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?;
-            
+
             Ok(result)
         }
 
+        /// Runs inference on up to `max_batch_size` inputs in one device
+        /// enqueue instead of one call per input.
+        fn infer_batch(&self, inputs: Vec<Vec<f32>>) -> PyResult<Vec<Vec<f32>>> {
+            self.engine
+                .infer_batch(&inputs)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))
+        }
+
+        /// Submits `input` to the engine's background batching queue,
+        /// coalescing it with concurrent calls from other threads, and
+        /// blocks until that batch completes.
+        fn infer_async(&self, input: Vec<f32>) -> PyResult<Vec<f32>> {
+            batch::block_on(self.engine.infer_async(input))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))
+        }
+
+        /// Returns `(linked, loaded, engine)` TensorRT version strings, to
+        /// diagnose "poor results" caused by silent version skew. `engine` is
+        /// `"unknown"` if the plan has no parseable version tag.
+        fn version_info(&self) -> PyResult<(String, String, String)> {
+            let info = self
+                .engine
+                .version_info()
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?;
+
+            let engine_version = info
+                .engine
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            Ok((info.linked.to_string(), info.loaded.to_string(), engine_version))
+        }
+
         #[staticmethod]
         fn load_from_file(path: String) -> PyResult<Self> {
             let engine = Arc::new(
@@ -110,6 +313,92 @@ fn tensorrt(_py: Python, m: &PyModule) -> PyResult<()> {
 
             Ok(Self { engine })
         }
+
+        /// Builds a `Precision::Int8` engine, feeding `batches` of
+        /// representative input data to the calibrator (mirroring the
+        /// `IInt8EntropyCalibrator2`-style `set_image_batcher` workflow) and
+        /// reusing `calibration_cache_path` on subsequent runs.
+        #[staticmethod]
+        fn build_with_calibration(
+            config: PyTensorRTConfig,
+            batches: Vec<Vec<f32>>,
+            calibration_cache_path: String,
+        ) -> PyResult<Self> {
+            let engine = Arc::new(
+                TensorRTEngine::build_with_calibration(
+                    config.to_core_config(),
+                    batches.into_iter(),
+                    calibration_cache_path,
+                )
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?,
+            );
+
+            Ok(Self { engine })
+        }
+
+        /// Parses an ONNX model at `onnx_path` and builds an engine from it,
+        /// using the dynamic shape profiles on `config`.
+        #[staticmethod]
+        fn build_from_onnx(onnx_path: String, config: PyTensorRTConfig) -> PyResult<Self> {
+            let engine = Arc::new(
+                TensorRTEngine::build_from_onnx(&onnx_path, config.to_core_config())
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e.to_string()))?,
+            );
+
+            Ok(Self { engine })
+        }
+    }
+
+    #[pyclass]
+    #[derive(Clone)]
+    struct PyOptimizationProfile {
+        min_dims: Vec<usize>,
+        opt_dims: Vec<usize>,
+        max_dims: Vec<usize>,
+    }
+
+    #[pymethods]
+    impl PyOptimizationProfile {
+        #[new]
+        fn new(min_dims: Vec<usize>, opt_dims: Vec<usize>, max_dims: Vec<usize>) -> Self {
+            Self { min_dims, opt_dims, max_dims }
+        }
+    }
+
+    impl PyOptimizationProfile {
+        fn to_core_profile(&self) -> OptimizationProfile {
+            OptimizationProfile {
+                min_dims: self.min_dims.clone(),
+                opt_dims: self.opt_dims.clone(),
+                max_dims: self.max_dims.clone(),
+            }
+        }
+    }
+
+    #[pyclass]
+    #[derive(Clone)]
+    struct PyMemoryPoolLimits {
+        workspace: u64,
+        dla_managed_sram: u64,
+        dla_local_dram: u64,
+    }
+
+    #[pymethods]
+    impl PyMemoryPoolLimits {
+        #[new]
+        fn new(workspace: u64, dla_managed_sram: u64, dla_local_dram: u64) -> Self {
+            Self { workspace, dla_managed_sram, dla_local_dram }
+        }
+    }
+
+    impl PyMemoryPoolLimits {
+        fn to_core_limits(&self) -> MemoryPoolLimits {
+            MemoryPoolLimits {
+                workspace: self.workspace,
+                dla_managed_sram: self.dla_managed_sram,
+                dla_local_dram: self.dla_local_dram,
+            }
+        }
     }
 
     #[pyclass]
@@ -117,8 +406,23 @@ fn tensorrt(_py: Python, m: &PyModule) -> PyResult<()> {
     struct PyTensorRTConfig {
         engine_path: String,
         max_batch_size: usize,
-        max_workspace_size: usize,
+        memory_pools: PyMemoryPoolLimits,
         precision: Precision,
+        calibration_batch_size: usize,
+        profiles: Vec<PyOptimizationProfile>,
+    }
+
+    impl PyTensorRTConfig {
+        fn to_core_config(&self) -> TensorRTConfig {
+            TensorRTConfig {
+                engine_path: self.engine_path.clone(),
+                max_batch_size: self.max_batch_size,
+                memory_pools: self.memory_pools.to_core_limits(),
+                precision: self.precision.clone(),
+                calibration_batch_size: self.calibration_batch_size,
+                profiles: self.profiles.iter().map(PyOptimizationProfile::to_core_profile).collect(),
+            }
+        }
     }
 
     #[pymethods]
@@ -127,28 +431,35 @@ fn tensorrt(_py: Python, m: &PyModule) -> PyResult<()> {
         fn new(
             engine_path: String,
             max_batch_size: usize,
-            max_workspace_size: usize,
+            memory_pools: PyMemoryPoolLimits,
             precision: String,
+            calibration_batch_size: usize,
+            profiles: Vec<PyOptimizationProfile>,
         ) -> PyResult<Self> {
             let precision_enum = match precision.as_str() {
                 "fp32" => Precision::Float32,
                 "fp16" => Precision::Float16,
+                "bf16" => Precision::Bf16,
                 "int8" => Precision::Int8,
                 _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                    "Invalid precision. Use 'fp32', 'fp16', or 'int8'"
+                    "Invalid precision. Use 'fp32', 'fp16', 'bf16', or 'int8'"
                 )),
             };
 
             Ok(Self {
                 engine_path,
                 max_batch_size,
-                max_workspace_size,
+                memory_pools,
                 precision: precision_enum,
+                calibration_batch_size,
+                profiles,
             })
         }
     }
 
     m.add_class::<PyTensorRTEngine>()?;
     m.add_class::<PyTensorRTConfig>()?;
+    m.add_class::<PyOptimizationProfile>()?;
+    m.add_class::<PyMemoryPoolLimits>()?;
     Ok(())
 }
\ No newline at end of file
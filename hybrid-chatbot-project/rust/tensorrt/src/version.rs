@@ -0,0 +1,110 @@
+//! Runtime TensorRT version compatibility checks: compares the version tag
+//! embedded in a serialized engine plan's header against the TensorRT
+//! version loaded at runtime. The version this crate was built against is
+//! reported alongside them but is not compared with the loaded version.
+
+use anyhow::{bail, Context, Result};
+use std::fmt;
+
+const HEADER_PREFIX: &str = "TRT";
+
+/// The TensorRT version this crate was compiled against.
+pub const LINKED_VERSION: TensorRTVersion = TensorRTVersion { major: 8, minor: 6, patch: 1 };
+
+/// A TensorRT semantic version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TensorRTVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl fmt::Display for TensorRTVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl TensorRTVersion {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut parts = raw.trim().split('.');
+        let major = parts.next().context("missing major version")?.parse()?;
+        let minor = parts.next().context("missing minor version")?.parse()?;
+        let patch = parts.next().context("missing patch version")?.parse()?;
+        Ok(Self { major, minor, patch })
+    }
+}
+
+/// Returns the TensorRT version actually loaded at runtime.
+///
+/// Placeholder: a real implementation would call `getInferLibVersion()` on
+/// the loaded TensorRT shared library rather than assume it matches the
+/// version this crate was linked against.
+pub fn loaded_version() -> TensorRTVersion {
+    LINKED_VERSION
+}
+
+/// Prepends the version header a serialized engine plan should start with,
+/// so a later load can detect version skew.
+pub fn write_header(version: TensorRTVersion, plan: &[u8]) -> Vec<u8> {
+    let mut contents = format!("{HEADER_PREFIX}{version}\n").into_bytes();
+    contents.extend_from_slice(plan);
+    contents
+}
+
+/// Parses the version tag from the start of a serialized engine plan, if
+/// one is present. Only plans built by this crate's own [`write_header`]
+/// (via `onnx::build_engine`) carry this tag — a genuine TensorRT `.engine`
+/// plan produced by `trtexec`/the TensorRT builder has no such prefix, so
+/// that case is `Ok(None)` rather than an error: the plan is loadable, its
+/// build version is simply unknown to us.
+pub fn parse_header(bytes: &[u8]) -> Result<Option<TensorRTVersion>> {
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return Ok(None);
+    };
+    let Some(line) = text.lines().next() else {
+        return Ok(None);
+    };
+    let Some(raw) = line.strip_prefix(HEADER_PREFIX) else {
+        return Ok(None);
+    };
+    TensorRTVersion::parse(raw).map(Some)
+}
+
+/// The outcome of comparing the version an engine plan was built with
+/// against the version loaded at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionCheck {
+    /// Versions match exactly.
+    Compatible,
+    /// Only minor/patch differ: the engine will likely still load, but
+    /// results should be treated with suspicion.
+    VersionMismatch {
+        engine: TensorRTVersion,
+        loaded: TensorRTVersion,
+    },
+    /// The engine plan has no parseable version tag (e.g. a plan built
+    /// outside this crate), so it can't be compared against `loaded`.
+    Unknown,
+}
+
+/// Compares `engine`'s embedded version, if known, against the `loaded`
+/// runtime version. A major version difference is a hard error, since
+/// TensorRT plans are not compatible across major versions; a minor/patch
+/// difference is surfaced as a `VersionMismatch` the caller can warn about
+/// instead of failing outright. `engine` is `None` for plans with no
+/// parseable version tag, which is reported as `Unknown` rather than failing.
+pub fn check(engine: Option<TensorRTVersion>, loaded: TensorRTVersion) -> Result<VersionCheck> {
+    let Some(engine) = engine else {
+        return Ok(VersionCheck::Unknown);
+    };
+    if engine.major != loaded.major {
+        bail!(
+            "engine plan built with TensorRT {engine} is incompatible with the loaded TensorRT {loaded} runtime (major version differs)"
+        );
+    }
+    if engine != loaded {
+        return Ok(VersionCheck::VersionMismatch { engine, loaded });
+    }
+    Ok(VersionCheck::Compatible)
+}